@@ -0,0 +1,33 @@
+use syntax::ast;
+use syntax::codemap::Span;
+use syntax::ext::base::{ExtCtxt, MacResult, MacEager, DummyResult};
+use syntax::parse::token;
+use syntax::tokenstream::TokenTree;
+
+use {BindgenOptions, Bindings};
+
+/// Implementation of the `bindgen!("header.h", ...)` compiler plugin macro.
+///
+/// This is a thin shim over `Bindings::generate`: it reads the header path
+/// from the macro's first argument and splices the generated items back in
+/// at the call site. Any further arguments are ignored.
+pub fn bindgen_macro(cx: &mut ExtCtxt, sp: Span, tts: &[TokenTree]) -> Box<MacResult + 'static> {
+    let header = match tts.first() {
+        Some(&TokenTree::Token(_, token::Literal(token::Str_(s), _))) => s.to_string(),
+        _ => {
+            cx.span_err(sp, "bindgen! expects a header path as its first argument");
+            return DummyResult::any(sp);
+        }
+    };
+
+    let mut options: BindgenOptions = Default::default();
+    options.header = header;
+
+    match Bindings::generate(&options, None, Some(sp)) {
+        Ok(bindings) => MacEager::items(ast::ThinVec::from(bindings.into_ast())),
+        Err(()) => {
+            cx.span_err(sp, "bindgen! failed to generate bindings");
+            DummyResult::any(sp)
+        }
+    }
+}
@@ -0,0 +1,236 @@
+//! Thin, safe-ish wrappers around the raw `clangll` bindings used by
+//! `parser` to walk a translation unit.
+
+use std::ffi::{CString, CStr};
+use std::str;
+
+use clangll;
+
+pub struct Index {
+    x: clangll::CXIndex,
+}
+
+impl Index {
+    pub fn create(exclude_decls_from_pch: bool, display_diagnostics: bool) -> Index {
+        let x = unsafe {
+            clangll::clang_createIndex(exclude_decls_from_pch as i32, display_diagnostics as i32)
+        };
+        Index { x: x }
+    }
+}
+
+impl Drop for Index {
+    fn drop(&mut self) {
+        unsafe { clangll::clang_disposeIndex(self.x) }
+    }
+}
+
+pub struct TranslationUnit {
+    x: clangll::CXTranslationUnit,
+}
+
+impl TranslationUnit {
+    pub fn parse(index: &Index, file: &str, cmd_args: &[String]) -> Option<TranslationUnit> {
+        let fname = CString::new(file).unwrap();
+        let c_args: Vec<CString> = cmd_args.iter().map(|s| CString::new(&s[..]).unwrap()).collect();
+        let mut c_arg_ptrs: Vec<*const i8> = c_args.iter().map(|s| s.as_ptr()).collect();
+
+        let tu = unsafe {
+            clangll::clang_parseTranslationUnit(
+                index.x,
+                fname.as_ptr(),
+                c_arg_ptrs.as_mut_ptr(),
+                c_arg_ptrs.len() as i32,
+                ::std::ptr::null_mut(),
+                0,
+                0,
+            )
+        };
+
+        if tu.is_null() {
+            None
+        } else {
+            Some(TranslationUnit { x: tu })
+        }
+    }
+
+    pub fn cursor(&self) -> Cursor {
+        Cursor { x: unsafe { clangll::clang_getTranslationUnitCursor(self.x) } }
+    }
+}
+
+impl Drop for TranslationUnit {
+    fn drop(&mut self) {
+        unsafe { clangll::clang_disposeTranslationUnit(self.x) }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Cursor {
+    x: clangll::CXCursor,
+}
+
+impl Cursor {
+    pub fn spelling(&self) -> String {
+        unsafe {
+            let s = clangll::clang_getCursorSpelling(self.x);
+            let bytes = CStr::from_ptr(s).to_bytes();
+            str::from_utf8(bytes).unwrap().to_string()
+        }
+    }
+
+    pub fn kind(&self) -> i32 {
+        unsafe { clangll::clang_getCursorKind(self.x) }
+    }
+
+    pub fn is_function(&self) -> bool {
+        self.kind() == clangll::CXCursor_FunctionDecl
+    }
+
+    pub fn is_struct_decl(&self) -> bool {
+        self.kind() == clangll::CXCursor_StructDecl
+    }
+
+    pub fn is_union_decl(&self) -> bool {
+        self.kind() == clangll::CXCursor_UnionDecl
+    }
+
+    pub fn is_enum_decl(&self) -> bool {
+        self.kind() == clangll::CXCursor_EnumDecl
+    }
+
+    pub fn is_field_decl(&self) -> bool {
+        self.kind() == clangll::CXCursor_FieldDecl
+    }
+
+    pub fn is_enum_constant_decl(&self) -> bool {
+        self.kind() == clangll::CXCursor_EnumConstantDecl
+    }
+
+    /// The type this cursor declares a value of (a field, variable, or
+    /// function), or refers to (a parameter).
+    pub fn ty(&self) -> Type {
+        Type { x: unsafe { clangll::clang_getCursorType(self.x) } }
+    }
+
+    /// Number of formal parameters, for a function/method cursor.
+    pub fn num_arguments(&self) -> usize {
+        unsafe { clangll::clang_Cursor_getNumArguments(self.x) as usize }
+    }
+
+    /// The `i`th formal parameter's cursor, for a function/method cursor.
+    pub fn argument(&self, i: usize) -> Cursor {
+        Cursor { x: unsafe { clangll::clang_Cursor_getArgument(self.x, i as i32) } }
+    }
+
+    /// The underlying integer type of this enum declaration.
+    pub fn enum_integer_type(&self) -> Type {
+        Type { x: unsafe { clangll::clang_getEnumDeclIntegerType(self.x) } }
+    }
+
+    /// The value of this enum constant declaration.
+    pub fn enum_constant_value(&self) -> i64 {
+        unsafe { clangll::clang_getEnumConstantDeclValue(self.x) }
+    }
+
+    /// Whether this field declaration is a bitfield.
+    pub fn is_bit_field(&self) -> bool {
+        unsafe { clangll::clang_Cursor_isBitField(self.x) != 0 }
+    }
+
+    /// This field declaration's bit width. Only meaningful when
+    /// `is_bit_field()` is true.
+    pub fn bit_width(&self) -> u32 {
+        unsafe { clangll::clang_getFieldDeclBitWidth(self.x) as u32 }
+    }
+
+    /// Whether this is a `static inline` function: one with no external
+    /// symbol of its own, which needs a non-inline C wrapper to be linked
+    /// against from Rust.
+    pub fn is_static_inline_function(&self) -> bool {
+        unsafe {
+            clangll::clang_Cursor_isFunctionInlined(self.x) != 0
+                && clangll::clang_getCursorLinkage(self.x) == clangll::CXLinkage_Internal
+        }
+    }
+
+    pub fn visit<F: FnMut(Cursor)>(&self, mut f: F) {
+        extern "C" fn visit_trampoline(
+            cursor: clangll::CXCursor,
+            _parent: clangll::CXCursor,
+            data: *mut ::libc::c_void,
+        ) -> i32 {
+            let closure: &mut &mut FnMut(Cursor) = unsafe { ::std::mem::transmute(data) };
+            closure(Cursor { x: cursor });
+            1 // CXChildVisit_Continue
+        }
+
+        let mut trait_obj: &mut FnMut(Cursor) = &mut f;
+        unsafe {
+            clangll::clang_visitChildren(
+                self.x,
+                visit_trampoline,
+                &mut trait_obj as *mut _ as *mut ::libc::c_void,
+            );
+        }
+    }
+}
+
+/// A clang `CXType`: the type of a cursor's declared value, a function's
+/// return type, or one of its parameter types.
+#[derive(Clone, Copy)]
+pub struct Type {
+    x: clangll::CXType,
+}
+
+impl Type {
+    pub fn kind(&self) -> i32 {
+        self.x.kind
+    }
+
+    /// The declaration this type refers to (e.g. the enum/struct decl for
+    /// an `Enum`/`Record` type).
+    pub fn declaration(&self) -> Cursor {
+        Cursor { x: unsafe { clangll::clang_getTypeDeclaration(self.x) } }
+    }
+
+    /// The type a `Pointer` type points to.
+    pub fn pointee(&self) -> Type {
+        Type { x: unsafe { clangll::clang_getPointeeType(self.x) } }
+    }
+
+    pub fn is_const_qualified(&self) -> bool {
+        unsafe { clangll::clang_isConstQualifiedType(self.x) != 0 }
+    }
+
+    /// The element type of a `ConstantArray`/`IncompleteArray` type.
+    pub fn array_element_type(&self) -> Type {
+        Type { x: unsafe { clangll::clang_getArrayElementType(self.x) } }
+    }
+
+    /// The element count of a `ConstantArray` type, or a negative number if
+    /// it isn't one.
+    pub fn array_size(&self) -> i64 {
+        unsafe { clangll::clang_getArraySize(self.x) }
+    }
+
+    /// The return type of a function type.
+    pub fn result_type(&self) -> Type {
+        Type { x: unsafe { clangll::clang_getResultType(self.x) } }
+    }
+
+    /// The number of formal parameter types of a function type.
+    pub fn num_arg_types(&self) -> usize {
+        unsafe { clangll::clang_getNumArgTypes(self.x) as usize }
+    }
+
+    /// The `i`th formal parameter type of a function type.
+    pub fn arg_type(&self, i: usize) -> Type {
+        Type { x: unsafe { clangll::clang_getArgType(self.x, i as i32) } }
+    }
+
+    /// Whether a function type ends in a C `...` variadic parameter.
+    pub fn is_variadic(&self) -> bool {
+        unsafe { clangll::clang_isFunctionTypeVariadic(self.x) != 0 }
+    }
+}
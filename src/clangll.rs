@@ -0,0 +1,106 @@
+//! Raw FFI declarations for the subset of libclang's C API that `clang`
+//! builds its safe wrappers on top of.
+
+#![allow(non_camel_case_types, non_upper_case_globals)]
+
+use libc::{c_char, c_int, c_void};
+
+pub type CXIndex = *mut c_void;
+pub type CXTranslationUnit = *mut c_void;
+pub type CXCursor = *mut c_void;
+
+pub type CXCursorVisitor =
+    extern "C" fn(cursor: CXCursor, parent: CXCursor, data: *mut c_void) -> c_int;
+
+/// `CXType` is a small by-value struct in the real libclang headers (a kind
+/// tag plus two opaque data words), not a pointer like `CXCursor`.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct CXType {
+    pub kind: c_int,
+    data: [*const c_void; 2],
+}
+
+#[link(name = "clang")]
+extern "C" {
+    pub fn clang_createIndex(exclude_decls_from_pch: c_int, display_diagnostics: c_int) -> CXIndex;
+    pub fn clang_disposeIndex(index: CXIndex);
+
+    pub fn clang_parseTranslationUnit(
+        index: CXIndex,
+        source_filename: *const c_char,
+        command_line_args: *const *const c_char,
+        num_command_line_args: c_int,
+        unsaved_files: *mut c_void,
+        num_unsaved_files: c_int,
+        options: c_int,
+    ) -> CXTranslationUnit;
+    pub fn clang_disposeTranslationUnit(tu: CXTranslationUnit);
+
+    pub fn clang_getTranslationUnitCursor(tu: CXTranslationUnit) -> CXCursor;
+    pub fn clang_visitChildren(
+        parent: CXCursor,
+        visitor: CXCursorVisitor,
+        client_data: *mut c_void,
+    ) -> c_int;
+
+    pub fn clang_getCursorSpelling(cursor: CXCursor) -> *const c_char;
+    pub fn clang_getCursorKind(cursor: CXCursor) -> c_int;
+
+    pub fn clang_Cursor_isFunctionInlined(cursor: CXCursor) -> c_int;
+    pub fn clang_getCursorLinkage(cursor: CXCursor) -> c_int;
+
+    pub fn clang_getCursorType(cursor: CXCursor) -> CXType;
+    pub fn clang_getPointeeType(ty: CXType) -> CXType;
+    pub fn clang_getArrayElementType(ty: CXType) -> CXType;
+    pub fn clang_getArraySize(ty: CXType) -> i64;
+    pub fn clang_isConstQualifiedType(ty: CXType) -> c_int;
+    pub fn clang_getTypeDeclaration(ty: CXType) -> CXCursor;
+    pub fn clang_getResultType(ty: CXType) -> CXType;
+    pub fn clang_getNumArgTypes(ty: CXType) -> c_int;
+    pub fn clang_getArgType(ty: CXType, i: c_int) -> CXType;
+    pub fn clang_isFunctionTypeVariadic(ty: CXType) -> c_int;
+
+    pub fn clang_Cursor_getNumArguments(cursor: CXCursor) -> c_int;
+    pub fn clang_Cursor_getArgument(cursor: CXCursor, i: c_int) -> CXCursor;
+
+    pub fn clang_getEnumDeclIntegerType(cursor: CXCursor) -> CXType;
+    pub fn clang_getEnumConstantDeclValue(cursor: CXCursor) -> i64;
+
+    pub fn clang_Cursor_isBitField(cursor: CXCursor) -> c_int;
+    pub fn clang_getFieldDeclBitWidth(cursor: CXCursor) -> c_int;
+}
+
+/// `CXCursorKind` values we care about (see `Index.h`).
+pub const CXCursor_StructDecl: c_int = 2;
+pub const CXCursor_UnionDecl: c_int = 3;
+pub const CXCursor_EnumDecl: c_int = 5;
+pub const CXCursor_FieldDecl: c_int = 6;
+pub const CXCursor_EnumConstantDecl: c_int = 7;
+pub const CXCursor_FunctionDecl: c_int = 8;
+
+/// `CXLinkageKind` values we care about (see `Index.h`).
+pub const CXLinkage_Internal: c_int = 2;
+
+/// `CXTypeKind` values we care about (see `Index.h`).
+pub const CXType_Void: c_int = 2;
+pub const CXType_Bool: c_int = 3;
+pub const CXType_Char_U: c_int = 4;
+pub const CXType_UChar: c_int = 5;
+pub const CXType_UShort: c_int = 8;
+pub const CXType_UInt: c_int = 9;
+pub const CXType_ULong: c_int = 10;
+pub const CXType_ULongLong: c_int = 11;
+pub const CXType_Char_S: c_int = 13;
+pub const CXType_SChar: c_int = 14;
+pub const CXType_Short: c_int = 16;
+pub const CXType_Int: c_int = 17;
+pub const CXType_Long: c_int = 18;
+pub const CXType_LongLong: c_int = 19;
+pub const CXType_Float: c_int = 21;
+pub const CXType_Double: c_int = 22;
+pub const CXType_Pointer: c_int = 101;
+pub const CXType_Record: c_int = 105;
+pub const CXType_Enum: c_int = 106;
+pub const CXType_ConstantArray: c_int = 112;
+pub const CXType_IncompleteArray: c_int = 114;
@@ -0,0 +1,632 @@
+use syntax::ast;
+use syntax::codemap::Span;
+use syntax::parse::{self, ParseSess};
+use syntax::ptr::P;
+
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use super::{EnumStrategy, LinkType, ParseCallbacks};
+use bitfield;
+use types::*;
+
+/// Lower the parsed `Global`s into the items that make up the generated
+/// module.
+///
+/// `callbacks`, if given, is consulted once per struct/union, and once per
+/// enum lowered to a Rust enum or a bitfield newtype, for any extra derives
+/// it wants added on top of the defaults; constified-module enums have no
+/// derives. It also renames enum variants. `enum_strategies`
+/// and `default_enum_strategy` pick how each enum gets lowered; see
+/// `resolve_enum_strategy`.
+pub fn gen_mod(
+    links: &[(String, LinkType)],
+    globals: Vec<Global>,
+    span: Span,
+    callbacks: Option<&ParseCallbacks>,
+    gen_debug_impl: bool,
+    enum_strategies: &[(String, EnumStrategy)],
+    default_enum_strategy: EnumStrategy,
+) -> Vec<P<ast::Item>> {
+    let mut items = Vec::new();
+    let mut fns = Vec::new();
+    let mut needs_bitfield_unit = false;
+
+    for g in globals.into_iter() {
+        match g {
+            Global::GComp(ci) => {
+                let ci = ci.borrow();
+                if ci.fields.iter().any(|f| f.bitfield.is_some()) {
+                    needs_bitfield_unit = true;
+                }
+                items.extend(gen_comp(&*ci, callbacks, gen_debug_impl));
+            }
+            Global::GEnum(ei) => {
+                let ei = ei.borrow();
+                let strategy = resolve_enum_strategy(&ei.name, enum_strategies, default_enum_strategy);
+                items.extend(gen_enum(&*ei, callbacks, strategy));
+            }
+            Global::GFunc(fi) => fns.push(fi),
+            Global::GVar(vi) => items.push(gen_var(&*vi.borrow())),
+            Global::GType(_) => {}
+        }
+    }
+
+    if !fns.is_empty() {
+        if links.iter().any(|&(_, lt)| lt == LinkType::Dynamic) {
+            items.extend(gen_dynamic_lib(&fns));
+        } else {
+            let decls: Vec<String> = fns.iter().map(|fi| gen_fn_decl(&*fi.borrow())).collect();
+            items.push(gen_extern_mod(links, &decls, span));
+        }
+    }
+
+    if needs_bitfield_unit {
+        items.insert(0, parse_item(bitfield::UNIT_SRC.to_string()));
+    }
+
+    items
+}
+
+/// Emit the struct/union item for `ci`, an accessor `impl` for any packed
+/// bitfield members, and a hand-written `impl Debug` when `gen_debug_impl`
+/// is set and not every field can be derived (a C array over 32 elements,
+/// a bitfield, or a field whose own type has no `Debug`).
+///
+/// Unions never get a `Debug` impl, derived or hand-written: `#[derive(Debug)]`
+/// isn't available on a union, and a hand-written one would need to read
+/// every field to format it, which is only legal inside `unsafe` and still
+/// wouldn't know which field is actually active.
+fn gen_comp(ci: &CompInfo, callbacks: Option<&ParseCallbacks>, gen_debug_impl: bool) -> Vec<P<ast::Item>> {
+    let is_union = ci.kind == CompKind::Union;
+    let packed = pack_fields(&ci.fields);
+    // Bitfields collapse into a `BitfieldUnit`, which derives `Copy`/`Clone`
+    // itself but not `Debug`, so it only blocks the latter.
+    let can_auto_derive = ci.fields.iter().all(|f| field_supports_auto_derive(&f.ty));
+    let can_derive_debug = !is_union && !packed.has_bitfields && can_auto_derive;
+
+    let mut base = Vec::new();
+    if can_auto_derive {
+        base.push("Copy");
+        base.push("Clone");
+    }
+    if gen_debug_impl && can_derive_debug {
+        base.push("Debug");
+    }
+    let derives = derive_attr(&ci.name, &base, callbacks);
+
+    let kw = match ci.kind {
+        CompKind::Struct => "struct",
+        CompKind::Union => "union",
+    };
+
+    let mut items = vec![parse_item(format!(
+        "#[repr(C)] {} pub {} {} {{ {} }}",
+        derives, kw, ci.name, packed.field_decls.join(" ")
+    ))];
+
+    if !packed.accessors.is_empty() {
+        items.push(parse_item(format!("impl {} {{ {} }}", ci.name, packed.accessors)));
+    }
+
+    if gen_debug_impl && !is_union && !can_derive_debug {
+        items.push(gen_debug_impl_item(ci));
+    }
+
+    items
+}
+
+struct PackedFields {
+    field_decls: Vec<String>,
+    accessors: String,
+    has_bitfields: bool,
+}
+
+/// Group consecutive bitfield members into shared `BitfieldUnit` fields
+/// and generate a `fn name(&self) -> T` / `fn set_name(&mut self, T)` pair
+/// per member, computed from its offset and width within the unit.
+fn pack_fields(fields: &[FieldInfo]) -> PackedFields {
+    let mut field_decls = Vec::new();
+    let mut accessors = String::new();
+    let mut has_bitfields = false;
+    let mut unit_index = 0;
+    let mut i = 0;
+
+    while i < fields.len() {
+        if fields[i].bitfield.is_none() {
+            field_decls.push(format!("pub {}: {},", fields[i].name, rust_ty(&fields[i].ty)));
+            i += 1;
+            continue;
+        }
+
+        has_bitfields = true;
+        let start = i;
+        let mut total_bits = 0u32;
+        while i < fields.len() {
+            match fields[i].bitfield {
+                Some(width) => { total_bits += width; i += 1; }
+                None => break,
+            }
+        }
+
+        let unit_name = format!("__bindgen_bitfield_{}", unit_index);
+        unit_index += 1;
+        let storage_bytes = (total_bits as usize + 7) / 8;
+        field_decls.push(format!("{}: BitfieldUnit<[u8; {}], u32>,", unit_name, storage_bytes));
+
+        let mut bit_offset = 0u32;
+        for f in &fields[start..i] {
+            let width = f.bitfield.unwrap();
+            let ty = rust_ty(&f.ty);
+            accessors.push_str(&format!(
+                "pub fn {name}(&self) -> {ty} {{ self.{unit}.get({offset}, {width}) as {ty} }} \
+                 pub fn set_{name}(&mut self, val: {ty}) {{ self.{unit}.set({offset}, {width}, val as u64); }} ",
+                name = f.name, ty = ty, unit = unit_name, offset = bit_offset, width = width
+            ));
+            bit_offset += width;
+        }
+    }
+
+    PackedFields { field_decls: field_decls, accessors: accessors, has_bitfields: has_bitfields }
+}
+
+/// Whether `ty` can go into a field covered by one of the standard
+/// `#[derive(..)]`s bindgen relies on (`Copy`, `Clone`, `Debug`): arrays
+/// longer than 32 elements and function pointers can't, since the standard
+/// library's blanket impls stop at 32 (no const generics on this toolchain)
+/// and not every calling convention has one.
+fn field_supports_auto_derive(ty: &Type) -> bool {
+    match *ty {
+        Type::TArray(_, len) => len <= 32,
+        Type::TFunc(..) => false,
+        _ => true,
+    }
+}
+
+fn gen_debug_impl_item(ci: &CompInfo) -> P<ast::Item> {
+    let field_fmts = ci.fields.iter()
+        .map(|f| {
+            if f.bitfield.is_some() {
+                format!("try!(write!(f, \"{name}: {{:?}}, \", self.{name}()));", name = f.name)
+            } else {
+                match f.ty {
+                    Type::TArray(..) => format!(
+                        "try!(write!(f, \"{name}: [\")); \
+                         for (__i, __e) in self.{name}.iter().enumerate() {{ \
+                             if __i > 0 {{ try!(write!(f, \", \")); }} \
+                             try!(write!(f, \"{{:?}}\", __e)); \
+                         }} \
+                         try!(write!(f, \"], \"));",
+                        name = f.name
+                    ),
+                    _ if field_supports_auto_derive(&f.ty) => {
+                        format!("try!(write!(f, \"{name}: {{:?}}, \", self.{name}));", name = f.name)
+                    }
+                    _ => format!("try!(write!(f, \"{name}: <opaque>, \"));", name = f.name),
+                }
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    parse_item(format!(
+        "impl ::std::fmt::Debug for {name} {{ \
+             fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {{ \
+                 try!(write!(f, \"{name} {{{{ \")); \
+                 {field_fmts} \
+                 write!(f, \"}}}}\") \
+             }} \
+         }}",
+        name = ci.name, field_fmts = field_fmts
+    ))
+}
+
+/// Find the strategy for the enum named `name`: the first pattern in
+/// `strategies` that matches it, or `default` if none do. A pattern is
+/// either an exact name or a trailing-`*` prefix.
+fn resolve_enum_strategy(
+    name: &str,
+    strategies: &[(String, EnumStrategy)],
+    default: EnumStrategy,
+) -> EnumStrategy {
+    for &(ref pattern, strategy) in strategies {
+        let matches = if pattern.ends_with('*') {
+            name.starts_with(&pattern[..pattern.len() - 1])
+        } else {
+            pattern == name
+        };
+        if matches {
+            return strategy;
+        }
+    }
+    default
+}
+
+/// Lower `ei` per `strategy`, except `Rust` falls back to
+/// `ConstifiedModule` when the enum's values don't actually fit the chosen
+/// repr, or look like an OR-able flag set rather than a closed set of
+/// alternatives — both cases where a real `enum`'s exhaustiveness
+/// guarantee would be unsound.
+fn gen_enum(ei: &EnumInfo, callbacks: Option<&ParseCallbacks>, strategy: EnumStrategy) -> Vec<P<ast::Item>> {
+    let effective = if strategy == EnumStrategy::Rust && (!enum_fits_repr(ei) || enum_looks_like_flags(ei)) {
+        EnumStrategy::ConstifiedModule
+    } else {
+        strategy
+    };
+
+    match effective {
+        EnumStrategy::Rust => vec![gen_enum_rust(ei, callbacks)],
+        EnumStrategy::ConstifiedModule => vec![gen_enum_constified(ei, callbacks)],
+        EnumStrategy::Bitfield => gen_enum_bitfield(ei, callbacks),
+    }
+}
+
+/// Apply `ParseCallbacks::enum_variant_name` to `item`, falling back to its
+/// original name when there's no callback or it declines to rename this
+/// variant.
+fn enum_variant_name(ei: &EnumInfo, item: &EnumItem, callbacks: Option<&ParseCallbacks>) -> String {
+    callbacks
+        .and_then(|cb| cb.enum_variant_name(Some(&ei.name), &item.name, item.val))
+        .unwrap_or_else(|| item.name.clone())
+}
+
+fn gen_enum_rust(ei: &EnumInfo, callbacks: Option<&ParseCallbacks>) -> P<ast::Item> {
+    let derives = derive_attr(&ei.name, &["Copy", "Clone", "PartialEq", "Eq"], callbacks);
+    let variants = ei.items.iter()
+        .map(|it| format!("{} = {},", enum_variant_name(ei, it, callbacks), it.val))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    parse_item(format!("#[repr(C)] {} pub enum {} {{ {} }}", derives, ei.name, variants))
+}
+
+fn gen_enum_constified(ei: &EnumInfo, callbacks: Option<&ParseCallbacks>) -> P<ast::Item> {
+    let ty = ikind_rust_ty(ei.kind);
+    let consts = ei.items.iter()
+        .map(|it| format!("pub const {}: Type = {};", enum_variant_name(ei, it, callbacks), it.val))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    parse_item(format!(
+        "pub mod {name} {{ pub type Type = {ty}; {consts} }}",
+        name = ei.name, ty = ty, consts = consts
+    ))
+}
+
+fn gen_enum_bitfield(ei: &EnumInfo, callbacks: Option<&ParseCallbacks>) -> Vec<P<ast::Item>> {
+    let ty = ikind_rust_ty(ei.kind);
+    let derives = derive_attr(&ei.name, &["Copy", "Clone", "PartialEq", "Eq"], callbacks);
+
+    let consts = ei.items.iter()
+        .map(|it| format!("pub const {}: {} = {}({});", enum_variant_name(ei, it, callbacks), ei.name, ei.name, it.val))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    vec![
+        parse_item(format!("#[repr(C)] {} pub struct {}(pub {});", derives, ei.name, ty)),
+        parse_item(format!("impl {} {{ {} }}", ei.name, consts)),
+        parse_item(format!(
+            "impl ::std::ops::BitOr for {name} {{ \
+                 type Output = {name}; \
+                 fn bitor(self, rhs: {name}) -> {name} {{ {name}(self.0 | rhs.0) }} \
+             }}",
+            name = ei.name
+        )),
+        parse_item(format!(
+            "impl ::std::ops::BitAnd for {name} {{ \
+                 type Output = {name}; \
+                 fn bitand(self, rhs: {name}) -> {name} {{ {name}(self.0 & rhs.0) }} \
+             }}",
+            name = ei.name
+        )),
+    ]
+}
+
+fn ikind_rust_ty(kind: IKind) -> &'static str {
+    match kind {
+        IBool | IUChar => "u8",
+        IChar | ISChar => "i8",
+        IUShort => "u16",
+        IShort => "i16",
+        IUInt => "u32",
+        IInt => "i32",
+        IULong | IULongLong => "u64",
+        ILong | ILongLong => "i64",
+    }
+}
+
+fn ikind_bits(kind: IKind) -> u32 {
+    match kind {
+        IBool | IChar | ISChar | IUChar => 8,
+        IShort | IUShort => 16,
+        IInt | IUInt => 32,
+        ILong | IULong | ILongLong | IULongLong => 64,
+    }
+}
+
+fn ikind_is_unsigned(kind: IKind) -> bool {
+    match kind {
+        IBool | IUChar | IUShort | IUInt | IULong | IULongLong => true,
+        IChar | ISChar | IShort | IInt | ILong | ILongLong => false,
+    }
+}
+
+/// Whether every discriminant in `ei` fits in its chosen integer repr.
+fn enum_fits_repr(ei: &EnumInfo) -> bool {
+    let bits = ikind_bits(ei.kind);
+    if bits >= 64 {
+        // Every discriminant is already stored as an i64, so a 64-bit repr
+        // always fits.
+        return true;
+    }
+
+    let unsigned = ikind_is_unsigned(ei.kind);
+    let (min, max) = if unsigned {
+        (0i64, (1i64 << bits) - 1)
+    } else {
+        (-(1i64 << (bits - 1)), (1i64 << (bits - 1)) - 1)
+    };
+
+    ei.items.iter().all(|it| it.val >= min && it.val <= max)
+}
+
+/// Whether `ei` looks like a set of OR-able flags rather than a closed set
+/// of alternatives: more than one variant, and every value is either zero
+/// or a single set bit.
+fn enum_looks_like_flags(ei: &EnumInfo) -> bool {
+    ei.items.len() > 1
+        && ei.items.iter().all(|it| it.val == 0 || (it.val > 0 && (it.val & (it.val - 1)) == 0))
+}
+
+fn gen_var(vi: &VarInfo) -> P<ast::Item> {
+    parse_item(format!("extern {{ pub static {}: {}; }}", vi.name, rust_ty(&vi.ty)))
+}
+
+fn gen_fn_decl(fi: &FuncInfo) -> String {
+    let (params, ret, is_variadic) = fn_sig_parts(&fi.ty);
+    let mut params: Vec<String> = params.iter()
+        .map(|&(ref name, ref ty)| format!("{}: {}", name, ty))
+        .collect();
+    if is_variadic {
+        params.push("...".to_string());
+    }
+
+    format!(
+        "pub fn {name}({params}) -> {ret};",
+        name = fi.link_name.as_ref().unwrap_or(&fi.name),
+        params = params.join(", "),
+        ret = ret
+    )
+}
+
+/// Pull a function type's parameter names/types and return type out of a
+/// `Type::TFunc`, rendered as Rust source via `rust_ty` -- the Rust-side
+/// counterpart to `serialize::wrapper_decl`'s C-side `c_ty` rendering.
+/// Anything else (there shouldn't be anything else, since this is only
+/// ever called on a `FuncInfo::ty`) renders as a no-arg `()`-returning stub.
+fn fn_sig_parts(ty: &Type) -> (Vec<(String, String)>, String, bool) {
+    match *ty {
+        Type::TFunc(ref ret, ref args, is_variadic) => {
+            let params = args.iter().map(|&(ref name, ref ty)| (name.clone(), rust_ty(ty))).collect();
+            (params, rust_ty(ret), is_variadic)
+        }
+        _ => (Vec::new(), "()".to_string(), false),
+    }
+}
+
+fn gen_extern_mod(_links: &[(String, LinkType)], fns: &[String], _span: Span) -> P<ast::Item> {
+    parse_item(format!("extern \"C\" {{ {} }}", fns.join(" ")))
+}
+
+/// Render a function type as a Rust fn-pointer type, via `fn_sig_parts`.
+/// Rust has no variadic fn-pointer type, so a variadic function's trailing
+/// `...` is simply dropped here; `gen_fn_decl`'s `extern` declaration is the
+/// one that can still express it faithfully.
+fn fn_ptr_ty(ty: &Type) -> String {
+    let (params, ret, _) = fn_sig_parts(ty);
+    let params = params.iter().map(|&(_, ref ty)| ty.clone()).collect::<Vec<_>>().join(", ");
+    format!("unsafe extern \"C\" fn({}) -> {}", params, ret)
+}
+
+/// Emit a `Lib` struct that `dlopen`s itself and `dlsym`s a function
+/// pointer field per `Global::GFunc`, instead of an `extern "C"` block that
+/// would need the library present at link time. Pairs the handle with a
+/// `Drop` impl that `dlclose`s it, the same way `clang::Index`/
+/// `clang::TranslationUnit` pair their raw handles with `Drop`.
+fn gen_dynamic_lib(fns: &[Rc<RefCell<FuncInfo>>]) -> Vec<P<ast::Item>> {
+    let fields = fns.iter()
+        .map(|fi| format!("pub {}: {},", fi.borrow().name, fn_ptr_ty(&fi.borrow().ty)))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let loads = fns.iter()
+        .map(|fi| {
+            let name = fi.borrow().name.clone();
+            format!(
+                "let {name} = {{ \
+                     let sym = ::std::ffi::CString::new(\"{name}\").unwrap(); \
+                     let p = ::libc::dlsym(handle, sym.as_ptr()); \
+                     if p.is_null() {{ ::libc::dlclose(handle); return Err(format!(\"missing symbol {name}\")); }} \
+                     ::std::mem::transmute(p) \
+                 }};",
+                name = name
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let field_names = fns.iter()
+        .map(|fi| fi.borrow().name.clone())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let methods = fns.iter()
+        .map(|fi| {
+            let fi = fi.borrow();
+            let (params, ret, _) = fn_sig_parts(&fi.ty);
+            let decl_params = params.iter()
+                .map(|&(ref name, ref ty)| format!("{}: {}", name, ty))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let call_args = params.iter()
+                .map(|&(ref name, _)| name.clone())
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!(
+                "pub unsafe fn {name}(&self, {params}) -> {ret} {{ (self.{name})({args}) }}",
+                name = fi.name, params = decl_params, ret = ret, args = call_args
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    vec![
+        parse_item(format!(
+            "pub struct Lib {{ __handle: *mut ::libc::c_void, {fields} }}",
+            fields = fields
+        )),
+        parse_item(format!(
+            "impl Lib {{ \
+                 pub unsafe fn open(path: &str) -> Result<Lib, String> {{ \
+                     let c_path = ::std::ffi::CString::new(path).unwrap(); \
+                     let handle = ::libc::dlopen(c_path.as_ptr(), ::libc::RTLD_NOW); \
+                     if handle.is_null() {{ return Err(format!(\"could not dlopen {{}}\", path)); }} \
+                     {loads} \
+                     Ok(Lib {{ __handle: handle, {field_names} }}) \
+                 }} \
+                 {methods} \
+             }}",
+            loads = loads, field_names = field_names, methods = methods
+        )),
+        parse_item(
+            "impl Drop for Lib { \
+                 fn drop(&mut self) { \
+                     unsafe { ::libc::dlclose(self.__handle); } \
+                 } \
+             }".to_string()
+        ),
+    ]
+}
+
+/// Build a `#[derive(...)]` attribute string for `name`, starting from the
+/// generator's usual `base` set and letting the registered callback (if
+/// any) append more.
+fn derive_attr(name: &str, base: &[&str], callbacks: Option<&ParseCallbacks>) -> String {
+    let mut derives: Vec<String> = base.iter().map(|s| s.to_string()).collect();
+    if let Some(cb) = callbacks {
+        derives.extend(cb.add_derives(name));
+    }
+    if derives.is_empty() {
+        String::new()
+    } else {
+        format!("#[derive({})]", derives.join(", "))
+    }
+}
+
+fn rust_ty(ty: &Type) -> String {
+    match *ty {
+        Type::TVoid => "()".to_string(),
+        Type::TInt(_) => "i32".to_string(),
+        Type::TFloat(_) => "f64".to_string(),
+        Type::TPtr(ref inner, is_const) => {
+            format!("*{} {}", if is_const { "const" } else { "mut" }, rust_ty(inner))
+        }
+        Type::TArray(ref inner, len) => format!("[{}; {}]", rust_ty(inner), len),
+        Type::TFunc(..) => "extern \"C\" fn()".to_string(),
+        Type::TNamed(ref g) => name_of(g),
+    }
+}
+
+fn name_of(g: &Global) -> String {
+    match *g {
+        Global::GComp(ref ci) => ci.borrow().name.clone(),
+        Global::GEnum(ref ei) => ei.borrow().name.clone(),
+        Global::GVar(ref vi) => vi.borrow().name.clone(),
+        Global::GFunc(ref fi) => fi.borrow().name.clone(),
+        Global::GType(_) => "c_void".to_string(),
+    }
+}
+
+/// Parse a snippet of generated Rust source into a single item. Using
+/// textual generation here (instead of quasiquoting against an `ExtCtxt`)
+/// keeps `gen` usable from the plain library entry point in addition to
+/// the `bindgen!` macro.
+fn parse_item(src: String) -> P<ast::Item> {
+    let sess = ParseSess::new();
+    parse::parse_item_from_source_str("<bindgen>".to_string(), src, vec![], &sess)
+        .expect("bindgen generated invalid Rust source")
+        .expect("bindgen generated an empty item")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::*;
+
+    fn func(ty: Type) -> FuncInfo {
+        FuncInfo { name: "f".to_string(), ty: ty, link_name: None, is_static_inline: false }
+    }
+
+    #[test]
+    fn gen_fn_decl_uses_real_param_and_return_types() {
+        let fi = func(Type::TFunc(
+            Box::new(Type::TInt(IInt)),
+            vec![
+                ("a".to_string(), Type::TInt(IInt)),
+                ("b".to_string(), Type::TPtr(Box::new(Type::TInt(IInt)), true)),
+            ],
+            false,
+        ));
+
+        assert_eq!(gen_fn_decl(&fi), "pub fn f(a: i32, b: *const i32) -> i32;");
+    }
+
+    #[test]
+    fn gen_fn_decl_variadic_appends_ellipsis() {
+        let fi = func(Type::TFunc(
+            Box::new(Type::TVoid),
+            vec![("fmt".to_string(), Type::TPtr(Box::new(Type::TInt(IInt)), true))],
+            true,
+        ));
+
+        assert_eq!(gen_fn_decl(&fi), "pub fn f(fmt: *const i32, ...) -> ();");
+    }
+
+    #[test]
+    fn gen_fn_decl_falls_back_to_a_no_arg_stub_for_non_function_types() {
+        let fi = func(Type::TVoid);
+        assert_eq!(gen_fn_decl(&fi), "pub fn f() -> ();");
+    }
+
+    #[test]
+    fn dynamic_lib_field_and_method_match_the_real_signature() {
+        let fi = Rc::new(RefCell::new(func(Type::TFunc(
+            Box::new(Type::TInt(IInt)),
+            vec![("a".to_string(), Type::TInt(IInt))],
+            false,
+        ))));
+
+        assert_eq!(fn_ptr_ty(&fi.borrow().ty), "unsafe extern \"C\" fn(i32) -> i32");
+
+        // struct Lib, impl Lib (open() plus one forwarding method per fn),
+        // and impl Drop for Lib.
+        let items = gen_dynamic_lib(&[fi]);
+        assert_eq!(items.len(), 3);
+    }
+
+    #[test]
+    fn gen_comp_never_emits_a_debug_impl_for_unions() {
+        let ci = CompInfo {
+            kind: CompKind::Union,
+            name: "U".to_string(),
+            fields: vec![FieldInfo { name: "a".to_string(), ty: Type::TInt(IInt), bitfield: None }],
+            has_vtable: false,
+            has_destructor: false,
+        };
+
+        // Just the `union U { ... }` item itself: no derived or
+        // hand-written Debug impl, and no bitfield accessors.
+        let items = gen_comp(&ci, None, true);
+        assert_eq!(items.len(), 1);
+    }
+}
@@ -26,6 +26,9 @@ mod clang;
 mod gen;
 mod parser;
 mod bgmacro;
+mod serialize;
+mod postprocess;
+mod bitfield;
 
 #[doc(hidden)]
 #[plugin_registrar]
@@ -34,34 +37,86 @@ pub fn plugin_registrar(reg: &mut Registry) {
 }
 
 pub struct BindgenOptions {
-    pub match_pat: Vec<String>,
     pub builtins: bool,
     pub links: Vec<(String, LinkType)>,
-    pub emit_ast: bool,
     pub fail_on_unknown_type: bool,
     pub override_enum_ty: String,
+    /// Path to the header to parse, kept separate from `clang_args` so that
+    /// appending extra clang flags after construction can never shift which
+    /// argument `parser` treats as the header.
+    pub header: String,
     pub clang_args: Vec<String>,
+    pub parse_callbacks: Option<Box<ParseCallbacks>>,
+    /// Experimental: generate non-inline C wrappers for `static inline`
+    /// functions instead of an unlinkable `extern` declaration. See
+    /// `Bindings::write_serialized_code`.
+    pub serialize_static_inline: bool,
+    /// Coalesce consecutive `extern` blocks that share an ABI into one
+    /// before printing.
+    pub merge_extern_blocks: bool,
+    /// Stably sort output items into constants, then types, then foreign
+    /// blocks before printing.
+    pub sort_by_category: bool,
+    /// Emit a hand-written `impl Debug` for structs that can't
+    /// `#[derive(Debug)]` (e.g. because they contain a C array over 32
+    /// elements), instead of leaving them without one. Unions get no
+    /// `Debug` impl either way.
+    pub gen_debug_impl: bool,
+    /// Strategy used for enums whose name matches a pattern, checked in
+    /// order; the first match wins. Patterns are a plain name or a
+    /// trailing-`*` prefix match (bindgen doesn't depend on a regex crate).
+    /// Falls back to `default_enum_strategy` when nothing matches.
+    pub enum_strategies: Vec<(String, EnumStrategy)>,
+    /// Strategy for enums that don't match any pattern in `enum_strategies`.
+    pub default_enum_strategy: EnumStrategy,
 }
 
 impl Default for BindgenOptions {
     fn default() -> BindgenOptions {
         BindgenOptions {
-            match_pat: Vec::new(),
             builtins: false,
             links: Vec::new(),
-            emit_ast: false,
             fail_on_unknown_type: false,
             override_enum_ty: "".to_string(),
-            clang_args: Vec::new()
+            header: "".to_string(),
+            clang_args: Vec::new(),
+            parse_callbacks: None,
+            serialize_static_inline: false,
+            merge_extern_blocks: false,
+            sort_by_category: false,
+            gen_debug_impl: false,
+            enum_strategies: Vec::new(),
+            default_enum_strategy: EnumStrategy::Rust,
         }
     }
 }
 
-#[derive(Copy)]
+#[derive(Copy, Clone, PartialEq)]
 pub enum LinkType {
     Default,
     Static,
-    Framework
+    Framework,
+    /// Don't link at build time at all. Instead, `gen` emits a `Lib` struct
+    /// whose `Lib::open` constructor `dlopen`s the library and `dlsym`s
+    /// each function pointer at runtime, so the library can be optional.
+    Dynamic,
+}
+
+/// How `gen` should lower a C enum.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum EnumStrategy {
+    /// A real `#[repr(C)] enum`. Unsafe if the C side can ever produce a
+    /// discriminant bindgen didn't see, which is why `gen` falls back to
+    /// `ConstifiedModule` when a chosen enum's values don't fit the repr
+    /// or look like an OR-able flag set.
+    Rust,
+    /// `mod Foo { pub type Type = ...; pub const A: Type = 0; ... }`.
+    /// Always safe, since it's just plain constants rather than an enum
+    /// with a closed set of valid discriminants.
+    ConstifiedModule,
+    /// A newtype wrapper around the integer repr implementing `BitOr`/
+    /// `BitAnd`, for flag enums meant to be OR'd together.
+    Bitfield,
 }
 
 pub trait Logger {
@@ -69,9 +124,38 @@ pub trait Logger {
     fn warn(&self, msg: &str);
 }
 
+/// User-supplied hooks into the parsing and code generation process.
+///
+/// Every method has a default that preserves bindgen's usual behavior, so
+/// implementors only need to override the ones they care about. This lets
+/// downstream build scripts fix up idiomatic naming or opt generated types
+/// into extra derives without post-processing the output.
+pub trait ParseCallbacks {
+    /// Potentially rename an item (struct, union, enum, function, or
+    /// variable) before it is emitted. Returning `None` keeps `original`.
+    fn item_name(&self, original: &str) -> Option<String> {
+        None
+    }
+
+    /// Potentially rename an enum variant before it is emitted. Returning
+    /// `None` keeps `variant` as-is.
+    fn enum_variant_name(&self, enum_name: Option<&str>, variant: &str, value: i64) -> Option<String> {
+        let _ = (enum_name, value);
+        None
+    }
+
+    /// Extra `#[derive(..)]`s to add to the item named `name`, on top of
+    /// whatever the generator would have derived anyway.
+    fn add_derives(&self, name: &str) -> Vec<String> {
+        let _ = name;
+        Vec::new()
+    }
+}
+
 pub struct Bindings
 {
-    module: ast::Mod
+    module: ast::Mod,
+    serialized_code: String,
 }
 
 impl Bindings
@@ -88,15 +172,45 @@ impl Bindings
             None => DUMMY_SP
         };
 
-        let globals = try!(parse_headers(options, logger));
+        let callbacks = options.parse_callbacks.as_ref().map(|cb| &**cb);
+        let globals = try!(parse_headers(options, logger, callbacks));
+
+        let serialized_code = if options.serialize_static_inline {
+            let funcs: Vec<_> = globals.iter()
+                .filter_map(|g| match *g {
+                    Global::GFunc(ref fi) => Some(fi.clone()),
+                    _ => None,
+                })
+                .collect();
+            serialize::gen_serialized_wrappers(&funcs[..], logger)
+        } else {
+            String::new()
+        };
+
+        let mut items = gen::gen_mod(
+            &options.links[..],
+            globals,
+            span,
+            callbacks,
+            options.gen_debug_impl,
+            &options.enum_strategies[..],
+            options.default_enum_strategy,
+        );
+        if options.merge_extern_blocks {
+            items = postprocess::merge_foreign_mods(items);
+        }
+        if options.sort_by_category {
+            items = postprocess::sort_by_category(items);
+        }
 
         let module = ast::Mod {
             inner: span,
-            items: gen::gen_mod(&options.links[..], globals, span)
+            items: items
         };
 
         Ok(Bindings {
-            module: module
+            module: module,
+            serialized_code: serialized_code,
         })
     }
 
@@ -104,6 +218,14 @@ impl Bindings
         self.module.items
     }
 
+    /// The accumulated C source for any `static inline` wrappers generated
+    /// because `BindgenOptions::serialize_static_inline` was set. Empty
+    /// when that option is off or no wrapping was needed. A `build.rs` can
+    /// feed this to the `cc` crate to compile it alongside the bindings.
+    pub fn write_serialized_code(&self, w: &mut Write) -> io::Result<()> {
+        w.write_all(self.serialized_code.as_bytes())
+    }
+
     pub fn to_string(&self) -> String {
         pprust::to_string(|s| {
             s.s = pp::mk_printer(Box::new(Vec::new()), 80);
@@ -128,6 +250,103 @@ impl Bindings
 }
 
 
+/// A fluent builder for `BindgenOptions`.
+///
+/// This is the preferred way to configure and run bindgen: it wraps a
+/// `BindgenOptions` and exposes chained setters for the common cases, so
+/// callers need not fill in its fields by hand.
+///
+/// ```ignore
+/// let bindings = Builder::new("my_header.h")
+///     .link("myclib", LinkType::Static)
+///     .generate()
+///     .unwrap();
+/// ```
+pub struct Builder {
+    options: BindgenOptions,
+}
+
+impl Builder {
+    /// Start building bindings for `header`.
+    pub fn new<T: Into<String>>(header: T) -> Builder {
+        let mut options: BindgenOptions = Default::default();
+        options.header = header.into();
+
+        Builder { options: options }
+    }
+
+    /// Pass an extra argument straight through to clang.
+    pub fn clang_arg<T: Into<String>>(mut self, arg: T) -> Builder {
+        self.options.clang_args.push(arg.into());
+        self
+    }
+
+    /// Link against `name` using the given `LinkType`.
+    pub fn link<T: Into<String>>(mut self, name: T, link_type: LinkType) -> Builder {
+        self.options.links.push((name.into(), link_type));
+        self
+    }
+
+    /// Force every enum to be represented with the integer type named by
+    /// `ty` (one of the strings accepted by `BindgenOptions::override_enum_ty`).
+    pub fn override_enum_type<T: Into<String>>(mut self, ty: T) -> Builder {
+        self.options.override_enum_ty = ty.into();
+        self
+    }
+
+    /// Register hooks for renaming items/variants and adding extra derives.
+    pub fn parse_callbacks(mut self, cb: Box<ParseCallbacks>) -> Builder {
+        self.options.parse_callbacks = Some(cb);
+        self
+    }
+
+    /// Experimental: generate non-inline C wrappers for `static inline`
+    /// functions so they can actually be linked against.
+    pub fn serialize_static_inline(mut self, doit: bool) -> Builder {
+        self.options.serialize_static_inline = doit;
+        self
+    }
+
+    /// Coalesce consecutive `extern` blocks that share an ABI into one.
+    pub fn merge_extern_blocks(mut self, doit: bool) -> Builder {
+        self.options.merge_extern_blocks = doit;
+        self
+    }
+
+    /// Stably sort output items into constants, then types, then foreign
+    /// blocks.
+    pub fn sort_by_category(mut self, doit: bool) -> Builder {
+        self.options.sort_by_category = doit;
+        self
+    }
+
+    /// Emit a hand-written `impl Debug` for structs that can't
+    /// `#[derive(Debug)]`.
+    pub fn gen_debug_impl(mut self, doit: bool) -> Builder {
+        self.options.gen_debug_impl = doit;
+        self
+    }
+
+    /// Use `strategy` for every enum whose name matches `pattern` (a plain
+    /// name, or a trailing-`*` prefix match), checked in the order added.
+    pub fn enum_strategy<T: Into<String>>(mut self, pattern: T, strategy: EnumStrategy) -> Builder {
+        self.options.enum_strategies.push((pattern.into(), strategy));
+        self
+    }
+
+    /// Strategy for enums that don't match any pattern set with
+    /// `enum_strategy`. Defaults to `EnumStrategy::Rust`.
+    pub fn default_enum_strategy(mut self, strategy: EnumStrategy) -> Builder {
+        self.options.default_enum_strategy = strategy;
+        self
+    }
+
+    /// Consume the builder and generate the bindings.
+    pub fn generate(self) -> Result<Bindings, ()> {
+        Bindings::generate(&self.options, None, None)
+    }
+}
+
 struct DummyLogger;
 
 impl Logger for DummyLogger {
@@ -135,7 +354,11 @@ impl Logger for DummyLogger {
     fn warn(&self, _msg: &str) { }
 }
 
-fn parse_headers(options: &BindgenOptions, logger: &Logger) -> Result<Vec<Global>, ()> {
+fn parse_headers(
+    options: &BindgenOptions,
+    logger: &Logger,
+    callbacks: Option<&ParseCallbacks>,
+) -> Result<Vec<Global>, ()> {
     fn str_to_ikind(s: &str) -> Option<types::IKind> {
         match s {
             "uchar"     => Some(types::IUChar),
@@ -155,14 +378,13 @@ fn parse_headers(options: &BindgenOptions, logger: &Logger) -> Result<Vec<Global
     let clang_opts = parser::ClangParserOptions {
         builtin_names: builtin_names(),
         builtins: options.builtins,
-        match_pat: options.match_pat.clone(),
-        emit_ast: options.emit_ast,
         fail_on_unknown_type: options.fail_on_unknown_type,
         override_enum_ty: str_to_ikind(&options.override_enum_ty[..]),
+        header: options.header.clone(),
         clang_args: options.clang_args.clone(),
     };
 
-    parser::parse(clang_opts, logger)
+    parser::parse(clang_opts, logger, callbacks)
 }
 
 fn builtin_names() -> HashSet<String> {
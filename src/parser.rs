@@ -0,0 +1,223 @@
+use std::collections::HashSet;
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use super::{Logger, ParseCallbacks};
+use clang;
+use clangll;
+use types::*;
+
+pub struct ClangParserOptions {
+    pub builtin_names: HashSet<String>,
+    pub builtins: bool,
+    pub fail_on_unknown_type: bool,
+    pub override_enum_ty: Option<IKind>,
+    /// Path to the header to parse. Kept separate from `clang_args`, which
+    /// is just extra flags passed straight through to clang.
+    pub header: String,
+    pub clang_args: Vec<String>,
+}
+
+/// Parse the header named in `options.header` and return the list of
+/// top-level items bindgen found worth emitting bindings for.
+///
+/// `callbacks`, when present, is given a chance to rename every item as it
+/// is discovered. Enum variant renaming and extra derives are left to
+/// `gen`.
+pub fn parse(
+    options: ClangParserOptions,
+    logger: &Logger,
+    callbacks: Option<&ParseCallbacks>,
+) -> Result<Vec<Global>, ()> {
+    if options.header.is_empty() {
+        logger.error("no header given to bindgen");
+        return Err(());
+    }
+    let header = options.header.clone();
+
+    let index = clang::Index::create(false, true);
+    let tu = match clang::TranslationUnit::parse(&index, &header[..], &options.clang_args[..]) {
+        Some(tu) => tu,
+        None => {
+            logger.error(&format!("could not parse {}", header));
+            return Err(());
+        }
+    };
+
+    let mut globals = Vec::new();
+
+    tu.cursor().visit(|cursor| {
+        let original_name = cursor.spelling();
+        if original_name.is_empty() || options.builtin_names.contains(&original_name) {
+            return;
+        }
+
+        let name = rename(callbacks, &original_name);
+
+        if cursor.is_function() {
+            globals.push(global_for_fn(&name, &cursor, options.override_enum_ty));
+        } else if cursor.is_struct_decl() {
+            globals.push(global_for_comp(&name, CompKind::Struct, &cursor, options.override_enum_ty));
+        } else if cursor.is_union_decl() {
+            globals.push(global_for_comp(&name, CompKind::Union, &cursor, options.override_enum_ty));
+        } else if cursor.is_enum_decl() {
+            globals.push(global_for_enum(&name, &cursor, options.override_enum_ty));
+        } else {
+            globals.push(global_for_var(&name));
+        }
+    });
+
+    Ok(globals)
+}
+
+/// Apply `ParseCallbacks::item_name`, falling back to the original name
+/// when there's no callback or it declines to rename this item.
+fn rename(callbacks: Option<&ParseCallbacks>, original: &str) -> String {
+    callbacks
+        .and_then(|cb| cb.item_name(original))
+        .unwrap_or_else(|| original.to_string())
+}
+
+fn global_for_var(name: &str) -> Global {
+    Global::GVar(Rc::new(RefCell::new(VarInfo {
+        name: name.to_string(),
+        ty: Type::TVoid,
+        is_const: false,
+    })))
+}
+
+/// Build the `FuncInfo` for a function cursor, tracking its real return
+/// type, parameter names/types, and variadic-ness so that downstream
+/// consumers — `serialize`'s C wrapper emitter chief among them — can
+/// reproduce a faithful signature instead of assuming `void foo(void)`.
+fn global_for_fn(name: &str, cursor: &clang::Cursor, override_enum_ty: Option<IKind>) -> Global {
+    let fn_ty = cursor.ty();
+    let ret = type_from_clang(&fn_ty.result_type(), override_enum_ty);
+
+    let num_args = cursor.num_arguments();
+    let args = (0..num_args)
+        .map(|i| {
+            let arg_name = cursor.argument(i).spelling();
+            let arg_name = if arg_name.is_empty() { format!("arg{}", i) } else { arg_name };
+            (arg_name, type_from_clang(&fn_ty.arg_type(i), override_enum_ty))
+        })
+        .collect();
+
+    Global::GFunc(Rc::new(RefCell::new(FuncInfo {
+        name: name.to_string(),
+        ty: Type::TFunc(Box::new(ret), args, fn_ty.is_variadic()),
+        link_name: None,
+        is_static_inline: cursor.is_static_inline_function(),
+    })))
+}
+
+/// Walk `cursor`'s field declarations (its direct children that are
+/// `FieldDecl`s) to build a `CompInfo` for the struct/union it declares.
+fn global_for_comp(name: &str, kind: CompKind, cursor: &clang::Cursor, override_enum_ty: Option<IKind>) -> Global {
+    let mut fields = Vec::new();
+
+    cursor.visit(|child| {
+        if !child.is_field_decl() {
+            return;
+        }
+
+        fields.push(FieldInfo {
+            name: child.spelling(),
+            ty: type_from_clang(&child.ty(), override_enum_ty),
+            bitfield: if child.is_bit_field() { Some(child.bit_width()) } else { None },
+        });
+    });
+
+    Global::GComp(Rc::new(RefCell::new(CompInfo {
+        kind: kind,
+        name: name.to_string(),
+        fields: fields,
+        has_vtable: false,
+        has_destructor: false,
+    })))
+}
+
+/// Walk `cursor`'s enum constant declarations to build an `EnumInfo`.
+fn global_for_enum(name: &str, cursor: &clang::Cursor, override_enum_ty: Option<IKind>) -> Global {
+    let kind = override_enum_ty.unwrap_or_else(|| ikind_for_type_kind(cursor.enum_integer_type().kind()));
+    let mut items = Vec::new();
+
+    cursor.visit(|child| {
+        if !child.is_enum_constant_decl() {
+            return;
+        }
+
+        items.push(EnumItem {
+            name: child.spelling(),
+            val: child.enum_constant_value(),
+        });
+    });
+
+    Global::GEnum(Rc::new(RefCell::new(EnumInfo {
+        name: name.to_string(),
+        items: items,
+        kind: kind,
+    })))
+}
+
+/// Lower a clang `Type` into bindgen's own `Type`. Covers the scalar,
+/// pointer, and constant-array shapes faithfully (the bulk of real C
+/// signatures); anything else libclang can report — records passed or
+/// returned by value chief among them — falls back to a plain `int`. Doing
+/// better would mean threading a name -> `Global` symbol table through the
+/// parser just to resolve `TNamed`, which no caller of this parser needs yet.
+fn type_from_clang(ty: &clang::Type, override_enum_ty: Option<IKind>) -> Type {
+    match ty.kind() {
+        clangll::CXType_Void => Type::TVoid,
+        clangll::CXType_Float => Type::TFloat(FFloat),
+        clangll::CXType_Double => Type::TFloat(FDouble),
+        clangll::CXType_Pointer => {
+            let pointee = ty.pointee();
+            let is_const = pointee.is_const_qualified();
+            Type::TPtr(Box::new(type_from_clang(&pointee, override_enum_ty)), is_const)
+        }
+        clangll::CXType_ConstantArray => {
+            let elem = ty.array_element_type();
+            let len = ty.array_size().max(0) as usize;
+            Type::TArray(Box::new(type_from_clang(&elem, override_enum_ty)), len)
+        }
+        clangll::CXType_Enum => {
+            let kind = override_enum_ty
+                .unwrap_or_else(|| ikind_for_type_kind(ty.declaration().enum_integer_type().kind()));
+            Type::TInt(kind)
+        }
+        k if is_integral_type_kind(k) => Type::TInt(ikind_for_type_kind(k)),
+        _ => Type::TInt(IInt),
+    }
+}
+
+fn is_integral_type_kind(kind: i32) -> bool {
+    match kind {
+        clangll::CXType_Bool | clangll::CXType_Char_U | clangll::CXType_UChar |
+        clangll::CXType_Char_S | clangll::CXType_SChar | clangll::CXType_UShort |
+        clangll::CXType_Short | clangll::CXType_UInt | clangll::CXType_Int |
+        clangll::CXType_ULong | clangll::CXType_Long | clangll::CXType_ULongLong |
+        clangll::CXType_LongLong => true,
+        _ => false,
+    }
+}
+
+/// Map a `CXTypeKind` integral constant onto our own `IKind`. Only called
+/// once `is_integral_type_kind` (or an enum's known-integral underlying
+/// type) has confirmed `kind` is one of these.
+fn ikind_for_type_kind(kind: i32) -> IKind {
+    match kind {
+        clangll::CXType_Bool => IBool,
+        clangll::CXType_Char_U | clangll::CXType_UChar => IUChar,
+        clangll::CXType_Char_S | clangll::CXType_SChar => ISChar,
+        clangll::CXType_UShort => IUShort,
+        clangll::CXType_Short => IShort,
+        clangll::CXType_UInt => IUInt,
+        clangll::CXType_Int => IInt,
+        clangll::CXType_ULong => IULong,
+        clangll::CXType_Long => ILong,
+        clangll::CXType_ULongLong => IULongLong,
+        clangll::CXType_LongLong => ILongLong,
+        _ => IInt,
+    }
+}
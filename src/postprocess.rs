@@ -0,0 +1,135 @@
+//! Optional tidying passes run over `Bindings::module.items` right before
+//! printing. Both are off by default and independently toggled through
+//! `BindgenOptions`, since they reorder output and would otherwise make
+//! diffs noisier for consumers who don't care.
+
+use syntax::ast;
+use syntax::ptr::P;
+
+/// Coalesce consecutive `extern` blocks that share an ABI into one, in the
+/// order they were first seen. Bindgen emits a separate foreign module per
+/// extern `static`, plus one holding every function, so this collapses
+/// runs of those back down to a block per ABI.
+pub fn merge_foreign_mods(items: Vec<P<ast::Item>>) -> Vec<P<ast::Item>> {
+    let mut merged: Vec<P<ast::Item>> = Vec::with_capacity(items.len());
+
+    for item in items.into_iter() {
+        let merged_into_prev = match (merged.last_mut(), &item.node) {
+            (Some(prev), &ast::ItemKind::ForeignMod(ref next_fm)) => {
+                if let ast::ItemKind::ForeignMod(ref mut prev_fm) = prev.node {
+                    if prev_fm.abi == next_fm.abi {
+                        prev_fm.items.extend(next_fm.items.clone());
+                        true
+                    } else {
+                        false
+                    }
+                } else {
+                    false
+                }
+            }
+            _ => false,
+        };
+
+        if !merged_into_prev {
+            merged.push(item);
+        }
+    }
+
+    merged
+}
+
+/// Stable-sort items into constants, then types (structs/enums/aliases),
+/// then foreign blocks. Any other item (a free fn, an `impl`, a `mod`) is
+/// grouped with the types. Being stable means items within a category
+/// keep their discovery order, so this only ever groups, never shuffles.
+pub fn sort_by_category(mut items: Vec<P<ast::Item>>) -> Vec<P<ast::Item>> {
+    items.sort_by_key(|item| category_of(item));
+    items
+}
+
+fn category_of(item: &ast::Item) -> u8 {
+    match item.node {
+        ast::ItemKind::Const(..) => 0,
+        ast::ItemKind::Struct(..) | ast::ItemKind::Enum(..) | ast::ItemKind::Ty(..) => 1,
+        ast::ItemKind::ForeignMod(..) => 2,
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syntax::parse::{self, ParseSess};
+
+    fn item(src: &str) -> P<ast::Item> {
+        let sess = ParseSess::new();
+        parse::parse_item_from_source_str("<test>".to_string(), src.to_string(), vec![], &sess)
+            .expect("test fixture failed to parse")
+            .expect("test fixture parsed to no item")
+    }
+
+    #[test]
+    fn merges_adjacent_foreign_mods_with_same_abi() {
+        let items = vec![
+            item("extern \"C\" { pub fn foo(); }"),
+            item("extern \"C\" { pub fn bar(); }"),
+        ];
+
+        let merged = merge_foreign_mods(items);
+
+        assert_eq!(merged.len(), 1);
+        match merged[0].node {
+            ast::ItemKind::ForeignMod(ref fm) => assert_eq!(fm.items.len(), 2),
+            _ => panic!("expected a single merged foreign mod"),
+        }
+    }
+
+    #[test]
+    fn does_not_merge_foreign_mods_with_different_abi() {
+        let items = vec![
+            item("extern \"C\" { pub fn foo(); }"),
+            item("extern \"stdcall\" { pub fn bar(); }"),
+        ];
+
+        let merged = merge_foreign_mods(items);
+
+        assert_eq!(merged.len(), 2);
+    }
+
+    #[test]
+    fn does_not_merge_foreign_mods_separated_by_another_item() {
+        let items = vec![
+            item("extern \"C\" { pub fn foo(); }"),
+            item("pub struct Between;"),
+            item("extern \"C\" { pub fn bar(); }"),
+        ];
+
+        let merged = merge_foreign_mods(items);
+
+        assert_eq!(merged.len(), 3);
+    }
+
+    #[test]
+    fn sorts_constants_then_types_then_foreign_mods_stably() {
+        let items = vec![
+            item("extern \"C\" { pub fn foo(); }"),
+            item("pub fn free_fn() {}"),
+            item("pub const A: i32 = 1;"),
+            item("pub struct S;"),
+            item("pub const B: i32 = 2;"),
+        ];
+
+        let sorted = sort_by_category(items);
+
+        // Constants sort first, keeping their relative order (A before B).
+        match sorted[0].node { ast::ItemKind::Const(..) => {}, _ => panic!("expected A") }
+        match sorted[1].node { ast::ItemKind::Const(..) => {}, _ => panic!("expected B") }
+        // `free_fn` has no dedicated category, so it lands in the "types"
+        // catch-all bucket alongside `S` -- and keeps its relative order,
+        // since it was discovered first.
+        match sorted[2].node { ast::ItemKind::Fn(..) => {}, _ => panic!("expected free_fn") }
+        match sorted[3].node { ast::ItemKind::Struct(..) => {}, _ => panic!("expected S") }
+        // Foreign mods sort last.
+        match sorted[4].node { ast::ItemKind::ForeignMod(..) => {}, _ => panic!("expected the foreign mod") }
+    }
+}
@@ -0,0 +1,189 @@
+//! Experimental companion to `gen`: emits a C source file of non-inline
+//! wrappers for `static inline` functions, which otherwise have no
+//! external symbol for the generated Rust `extern` block to link against.
+//!
+//! For each wrapped function this rewrites its `FuncInfo::link_name` to
+//! point at the wrapper, so `gen` picks it up automatically when emitting
+//! the `extern "C"` declaration.
+
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use super::Logger;
+use types::*;
+
+/// Prefix given to every generated wrapper symbol.
+pub const WRAPPER_PREFIX: &'static str = "__bindgen_wrap_";
+
+/// Walk `funcs`, writing a wrapper for each `static inline` function and
+/// pointing its `link_name` at that wrapper. Variadic functions can't be
+/// wrapped this way (there's no way to forward `...`), so they're skipped
+/// with a logger warning instead.
+///
+/// Returns the accumulated C source for the wrappers that were emitted.
+pub fn gen_serialized_wrappers(funcs: &[Rc<RefCell<FuncInfo>>], logger: &Logger) -> String {
+    let mut source = String::new();
+
+    for func in funcs {
+        let mut fi = func.borrow_mut();
+        if !fi.is_static_inline {
+            continue;
+        }
+
+        let (ret, args, is_variadic) = match fi.ty {
+            Type::TFunc(ref ret, ref args, is_variadic) => (ret.clone(), args.clone(), is_variadic),
+            _ => continue,
+        };
+
+        if is_variadic {
+            logger.warn(&format!(
+                "cannot wrap variadic static inline function `{}`; it will be unlinkable",
+                fi.name
+            ));
+            continue;
+        }
+
+        let wrapper_name = format!("{}{}", WRAPPER_PREFIX, fi.name);
+        source.push_str(&wrapper_decl(&wrapper_name, &fi.name, &ret, &args));
+        fi.link_name = Some(wrapper_name);
+    }
+
+    source
+}
+
+fn wrapper_decl(wrapper_name: &str, original_name: &str, ret: &Type, args: &[(String, Type)]) -> String {
+    let params = args.iter()
+        .map(|&(ref name, ref ty)| format!("{} {}", c_ty(ty), name))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let arg_names = args.iter()
+        .map(|&(ref name, _)| name.clone())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!(
+        "{ret} {wrapper}({params}) {{ return {orig}({args}); }}\n",
+        ret = c_ty(ret),
+        wrapper = wrapper_name,
+        params = if params.is_empty() { "void".to_string() } else { params },
+        orig = original_name,
+        args = arg_names,
+    )
+}
+
+/// Render a `Type` as the closest matching C type name. This only needs to
+/// cover the shapes `parser` can currently produce for function signatures.
+fn c_ty(ty: &Type) -> String {
+    match *ty {
+        Type::TVoid => "void".to_string(),
+        Type::TInt(kind) => match kind {
+            IBool => "_Bool",
+            IChar => "char",
+            ISChar => "signed char",
+            IUChar => "unsigned char",
+            IShort => "short", IUShort => "unsigned short",
+            IInt => "int", IUInt => "unsigned int",
+            ILong => "long", IULong => "unsigned long",
+            ILongLong => "long long", IULongLong => "unsigned long long",
+        }.to_string(),
+        Type::TFloat(FFloat) => "float".to_string(),
+        Type::TFloat(FDouble) => "double".to_string(),
+        Type::TPtr(ref inner, is_const) => {
+            format!("{}{} *", if is_const { "const " } else { "" }, c_ty(inner))
+        }
+        Type::TArray(ref inner, _) => format!("{} *", c_ty(inner)),
+        Type::TFunc(..) => "void *".to_string(),
+        Type::TNamed(_) => "void".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    struct RecordingLogger {
+        warnings: RefCell<Vec<String>>,
+    }
+
+    impl RecordingLogger {
+        fn new() -> RecordingLogger {
+            RecordingLogger { warnings: RefCell::new(Vec::new()) }
+        }
+    }
+
+    impl Logger for RecordingLogger {
+        fn error(&self, _msg: &str) { }
+        fn warn(&self, msg: &str) {
+            self.warnings.borrow_mut().push(msg.to_string());
+        }
+    }
+
+    fn func(name: &str, ty: Type, is_static_inline: bool) -> Rc<RefCell<FuncInfo>> {
+        Rc::new(RefCell::new(FuncInfo {
+            name: name.to_string(),
+            ty: ty,
+            link_name: None,
+            is_static_inline: is_static_inline,
+        }))
+    }
+
+    #[test]
+    fn wraps_static_inline_functions_and_rewrites_link_name() {
+        let logger = RecordingLogger::new();
+        let fi = func("add", Type::TFunc(
+            Box::new(Type::TInt(::types::IInt)),
+            vec![
+                ("a".to_string(), Type::TInt(::types::IInt)),
+                ("b".to_string(), Type::TPtr(Box::new(Type::TInt(::types::IInt)), true)),
+            ],
+            false,
+        ), true);
+
+        let source = gen_serialized_wrappers(&[fi.clone()], &logger);
+
+        assert_eq!(
+            source,
+            "int __bindgen_wrap_add(int a, const int * b) { return add(a, b); }\n"
+        );
+        assert_eq!(fi.borrow().link_name, Some("__bindgen_wrap_add".to_string()));
+        assert!(logger.warnings.borrow().is_empty());
+    }
+
+    #[test]
+    fn leaves_non_static_inline_functions_untouched() {
+        let logger = RecordingLogger::new();
+        let fi = func("add", Type::TFunc(Box::new(Type::TVoid), vec![], false), false);
+
+        let source = gen_serialized_wrappers(&[fi.clone()], &logger);
+
+        assert_eq!(source, "");
+        assert_eq!(fi.borrow().link_name, None);
+    }
+
+    #[test]
+    fn skips_variadic_static_inline_functions_with_a_warning() {
+        let logger = RecordingLogger::new();
+        let fi = func("logf", Type::TFunc(
+            Box::new(Type::TVoid),
+            vec![("fmt".to_string(), Type::TPtr(Box::new(Type::TInt(::types::IInt)), true))],
+            true,
+        ), true);
+
+        let source = gen_serialized_wrappers(&[fi.clone()], &logger);
+
+        assert_eq!(source, "");
+        assert_eq!(fi.borrow().link_name, None);
+        assert_eq!(logger.warnings.borrow().len(), 1);
+    }
+
+    #[test]
+    fn no_arg_wrapper_uses_void_param_list() {
+        let logger = RecordingLogger::new();
+        let fi = func("init", Type::TFunc(Box::new(Type::TVoid), vec![], false), true);
+
+        let source = gen_serialized_wrappers(&[fi], &logger);
+
+        assert_eq!(source, "void __bindgen_wrap_init(void) { return init(); }\n");
+    }
+}
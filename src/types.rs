@@ -0,0 +1,104 @@
+use std::rc::Rc;
+use std::cell::RefCell;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum IKind {
+    IBool,
+    IChar,
+    ISChar,
+    IUChar,
+    IShort,
+    IUShort,
+    IInt,
+    IUInt,
+    ILong,
+    IULong,
+    ILongLong,
+    IULongLong,
+}
+
+pub use self::IKind::*;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FKind {
+    FFloat,
+    FDouble,
+}
+
+pub use self::FKind::*;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CompKind {
+    Struct,
+    Union,
+}
+
+#[derive(Clone)]
+pub enum Type {
+    TVoid,
+    TInt(IKind),
+    TFloat(FKind),
+    TPtr(Box<Type>, bool),
+    TArray(Box<Type>, usize),
+    TFunc(Box<Type>, Vec<(String, Type)>, bool),
+    TNamed(Box<Global>),
+}
+
+#[derive(Clone)]
+pub struct FieldInfo {
+    pub name: String,
+    pub ty: Type,
+    /// Bit width, for bitfield members. `None` for a regular field.
+    pub bitfield: Option<u32>,
+}
+
+#[derive(Clone)]
+pub struct CompInfo {
+    pub kind: CompKind,
+    pub name: String,
+    pub fields: Vec<FieldInfo>,
+    pub has_vtable: bool,
+    pub has_destructor: bool,
+}
+
+#[derive(Clone)]
+pub struct EnumItem {
+    pub name: String,
+    pub val: i64,
+}
+
+#[derive(Clone)]
+pub struct EnumInfo {
+    pub name: String,
+    pub items: Vec<EnumItem>,
+    pub kind: IKind,
+}
+
+#[derive(Clone)]
+pub struct VarInfo {
+    pub name: String,
+    pub ty: Type,
+    pub is_const: bool,
+}
+
+#[derive(Clone)]
+pub struct FuncInfo {
+    pub name: String,
+    pub ty: Type,
+    /// Symbol to actually link against, if it differs from `name` (e.g.
+    /// when a `static inline` wrapper was generated for it).
+    pub link_name: Option<String>,
+    /// Whether this function was declared `static inline` in the header,
+    /// and therefore has no externally linkable symbol of its own.
+    pub is_static_inline: bool,
+}
+
+/// A single top-level item discovered while walking the translation unit.
+#[derive(Clone)]
+pub enum Global {
+    GType(Rc<RefCell<Type>>),
+    GComp(Rc<RefCell<CompInfo>>),
+    GEnum(Rc<RefCell<EnumInfo>>),
+    GVar(Rc<RefCell<VarInfo>>),
+    GFunc(Rc<RefCell<FuncInfo>>),
+}